@@ -0,0 +1,63 @@
+use color_eyre::eyre::Result;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::path::{Path, PathBuf};
+
+/// Compiled gitignore-style matchers gathered from `.chronocleanignore` files
+/// found at the root of each target folder, plus an optional explicit
+/// `--ignore-file`. Patterns are evaluated relative to the directory that
+/// contains the ignore file they came from, matching gitignore semantics
+/// (globs, directory-only patterns, negation, anchoring).
+///
+/// Unlike `git`/`fd`, a `.chronocleanignore` is only looked up at each
+/// target folder's root, not in every nested directory as the walk
+/// descends — a single root-level file governs the whole target folder.
+pub struct IgnoreMatchers {
+    matchers: Vec<Gitignore>,
+}
+
+impl IgnoreMatchers {
+    /// Loads the explicit `--ignore-file`, if any, plus each target folder's
+    /// root-level `.chronocleanignore`, if present. Does not look for
+    /// `.chronocleanignore` files in subdirectories of a target folder.
+    pub fn load(target_folders: &[PathBuf], explicit_ignore_file: Option<&Path>) -> Result<Self> {
+        let mut matchers = Vec::new();
+
+        if let Some(ignore_file) = explicit_ignore_file {
+            matchers.push(compile(ignore_file)?);
+        }
+
+        for target_folder in target_folders {
+            let default_ignore_file = target_folder.join(".chronocleanignore");
+            if default_ignore_file.is_file() {
+                matchers.push(compile(&default_ignore_file)?);
+            }
+        }
+
+        Ok(Self { matchers })
+    }
+
+    /// Returns true if `path`, or any of its parent directories, is matched
+    /// (and not subsequently negated) by any of the compiled ignore files.
+    /// Checking parents is what makes directory-only patterns like `cache/`
+    /// exclude everything nested under a matched directory.
+    /// Only matchers whose ignore file's directory contains `path` are consulted.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        let is_dir = path.is_dir();
+        let relative_path = path.strip_prefix("./").unwrap_or(path);
+        self.matchers.iter()
+            .filter(|matcher| {
+                let root = matcher.path();
+                relative_path.starts_with(root.strip_prefix("./").unwrap_or(root))
+            })
+            .any(|matcher| matcher.matched_path_or_any_parents(path, is_dir).is_ignore())
+    }
+}
+
+fn compile(ignore_file: &Path) -> Result<Gitignore> {
+    let base_dir = ignore_file.parent().unwrap_or_else(|| Path::new("."));
+    let mut builder = GitignoreBuilder::new(base_dir);
+    if let Some(error) = builder.add(ignore_file) {
+        return Err(error.into());
+    }
+    Ok(builder.build()?)
+}
@@ -0,0 +1,55 @@
+use color_eyre::eyre::Result;
+use env_logger::Target;
+use log::LevelFilter;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Initializes the `log` backend. `RUST_LOG` takes precedence when set;
+/// otherwise the level is derived from `-v`/`-q`. When `log_file` is given,
+/// every line is written to both the terminal and the file.
+pub fn init_logging(verbose: u8, quiet: bool, log_file: Option<&Path>) -> Result<()> {
+    let default_level = if quiet {
+        LevelFilter::Warn
+    } else {
+        match verbose {
+            0 => LevelFilter::Info,
+            1 => LevelFilter::Debug,
+            _ => LevelFilter::Trace,
+        }
+    };
+
+    let mut builder = env_logger::Builder::new();
+    builder.filter_level(default_level);
+    if let Ok(rust_log) = std::env::var("RUST_LOG") {
+        builder.parse_filters(&rust_log);
+    }
+    builder.format_timestamp_millis();
+
+    if let Some(log_file) = log_file {
+        let file = OpenOptions::new().create(true).append(true).open(log_file)?;
+        builder.target(Target::Pipe(Box::new(TeeWriter { file })));
+    }
+
+    builder.try_init()?;
+    Ok(())
+}
+
+/// Writes every line to both stdout and the `--log-file` destination, so
+/// passing a log file tees output instead of replacing it.
+struct TeeWriter {
+    file: File,
+}
+
+impl Write for TeeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        io::stdout().write_all(buf)?;
+        self.file.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stdout().flush()?;
+        self.file.flush()
+    }
+}
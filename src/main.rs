@@ -1,13 +1,29 @@
+use bytesize::ByteSize;
 use clap::Parser;
 use color_eyre::eyre;
 use color_eyre::eyre::Result;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use rayon::prelude::*;
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicU32, Ordering};
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 use walkdir::{DirEntry, WalkDir};
 
-mod log_macros;
+use ignore_rules::IgnoreMatchers;
+use log::{debug, error, info, warn};
+use progress::spawn_progress_reporter;
+use report::{Outcome, ReportEntry, ReportFormat, RunReport};
+use symlink_guard::SymlinkGuard;
+
+mod ignore_rules;
+mod logging;
+mod progress;
+mod report;
+mod symlink_guard;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -24,6 +40,9 @@ struct Cli {
     #[arg(long, value_name = "PATHS", value_delimiter = ',', help = "Add a file or folder as ignored, files ignored and files inside folders ignored will not be deleted")]
     ignored_paths: Option<Vec<PathBuf>>,
 
+    #[arg(long, value_name = "PATH", help = "A gitignore-style file with additional patterns to exclude from deletion, in addition to any .chronocleanignore found at the root of a target folder (nested .chronocleanignore files are not read)")]
+    ignore_file: Option<PathBuf>,
+
     #[arg(long, value_name = "DEPTH", help = "Minimum depth to search for files to delete")]
     min_depth: Option<usize>,
 
@@ -36,8 +55,53 @@ struct Cli {
     #[arg(long, default_value = "false", help = "Follow symbolic links (default: false)")]
     follow_symbolic_links: bool,
 
+    #[arg(long, value_name = "N", help = "Keep the N newest files in each directory, even if they are past the cutoff (mutually exclusive with --keep-oldest)")]
+    keep_newest: Option<usize>,
+
+    #[arg(long, value_name = "N", help = "Keep the N oldest files in each directory, even if they are past the cutoff (mutually exclusive with --keep-newest)")]
+    keep_oldest: Option<usize>,
+
+    #[arg(long, default_value = "trash", value_name = "METHOD", value_parser = delete_method_parser, help = "How to delete matched files and folders: trash (default), permanent, or move")]
+    delete_method: DeleteMethod,
+
+    #[arg(long, value_name = "PATH", help = "Directory files are relocated to when --delete-method is move; their path relative to the target folder is preserved")]
+    archive_dir: Option<PathBuf>,
+
     #[arg(long, default_value = "false", help = "Don't delete the files, just say which files would be deleted (default: false)")]
     dry_run: bool,
+
+    #[arg(short, long, action = clap::ArgAction::Count, help = "Increase log verbosity (-v for debug, -vv for trace); overridden by RUST_LOG")]
+    verbose: u8,
+
+    #[arg(short, long, default_value = "false", help = "Only log warnings and errors; overridden by RUST_LOG")]
+    quiet: bool,
+
+    #[arg(long, value_name = "PATH", help = "Also write log output to this file")]
+    log_file: Option<PathBuf>,
+
+    #[arg(long, value_name = "PATH", help = "Write a machine-readable report of the run (every file considered, its outcome, and summary counts) to this file")]
+    report_file: Option<PathBuf>,
+
+    #[arg(long, default_value = "json", value_name = "FORMAT", value_parser = report::report_format_parser, help = "Format for --report-file: json (default) or csv")]
+    report_format: ReportFormat,
+
+    #[arg(long, value_name = "SIZE", value_parser = byte_size_parser, help = "Only delete files at least this size (e.g. 10MB, 1.5GiB)")]
+    min_size: Option<u64>,
+
+    #[arg(long, value_name = "SIZE", value_parser = byte_size_parser, help = "Only delete files at most this size (e.g. 10MB, 1.5GiB)")]
+    max_size: Option<u64>,
+
+    #[arg(long, value_name = "EXTENSIONS", value_delimiter = ',', help = "Only delete files with one of these extensions (case-insensitive, without the dot)")]
+    extensions: Option<Vec<String>>,
+
+    #[arg(long, value_name = "EXTENSIONS", value_delimiter = ',', help = "Never delete files with one of these extensions (case-insensitive, without the dot)")]
+    excluded_extensions: Option<Vec<String>>,
+
+    #[arg(long, value_name = "GLOBS", value_delimiter = ',', help = "Only delete files whose name matches one of these globs (e.g. *.tmp,*.log)")]
+    name_globs: Option<Vec<String>>,
+
+    #[arg(long, value_name = "INTERVAL", value_parser = humantime::parse_duration, help = "Keep running, re-scanning the target folders every INTERVAL instead of exiting after one pass (e.g. 30m, 1h)")]
+    watch: Option<Duration>,
 }
 
 #[derive(Parser, Debug, PartialEq, Clone, Copy)]
@@ -58,20 +122,126 @@ fn file_date_type_parser(value: &str) -> Result<FileDateType, String> {
     }
 }
 
+#[derive(Parser, Debug, PartialEq, Clone, Copy)]
+#[clap(about = "How matched files and folders should be deleted", rename_all = "snake_case")]
+enum DeleteMethod {
+    Trash,
+    Permanent,
+    Move,
+}
+
+fn delete_method_parser(value: &str) -> Result<DeleteMethod, String> {
+    let trimmed_value = value.trim();
+    match trimmed_value.to_ascii_lowercase().as_str() {
+        "trash" => Ok(DeleteMethod::Trash),
+        "permanent" => Ok(DeleteMethod::Permanent),
+        "move" => Ok(DeleteMethod::Move),
+        _ => Err(format!("Unsupported delete method: {}. Please use one of the following: {}", trimmed_value, ["trash", "permanent", "move"].join(", "))),
+    }
+}
+
+fn byte_size_parser(value: &str) -> Result<u64, String> {
+    value.trim().parse::<ByteSize>()
+        .map(|size| size.0)
+        .map_err(|e| format!("Invalid size '{}': {}", value, e))
+}
+
 fn main() -> Result<()> {
     color_eyre::install()?;
     let cli = Cli::parse();
 
+    logging::init_logging(cli.verbose, cli.quiet, cli.log_file.as_deref())?;
+
     validate_arguments(&cli)?;
     print_arguments(&cli);
 
-    let files_to_delete = get_files_to_delete(&cli)?;
-    delete_files(&cli, &files_to_delete);
-    delete_empty_folders_in_target_folders(&cli)?;
- 
+    match cli.watch {
+        Some(interval) => run_watch_loop(&cli, interval),
+        None => run_pass(&cli),
+    }
+}
+
+/// Runs every target folder through the scan/delete/report pipeline once.
+fn run_pass(cli: &Cli) -> Result<()> {
+    let started_at = Instant::now();
+
+    let ignore_matchers = IgnoreMatchers::load(&cli.target_folders, cli.ignore_file.as_deref())?;
+    let name_globs = build_name_globs(&cli.name_globs)?;
+
+    // Each traversal phase gets its own `SymlinkGuard`: `visited_dirs` tracks
+    // directories seen so far, and sharing one guard across two independent
+    // walks of the same tree would make the second walk see every symlink
+    // the first walk already visited as a (spurious) loop.
+    let scan_symlink_guard = SymlinkGuard::new();
+    let (files_to_delete, skipped_entries) = get_files_to_delete(cli, &ignore_matchers, &scan_symlink_guard, name_globs.as_ref())?;
+    let (files_to_delete, retained_by_policy) = apply_retention_policy(cli, files_to_delete);
+
+    let mut report_entries: Vec<ReportEntry> = skipped_entries;
+    report_entries.extend(retained_by_policy.into_iter()
+        .map(|(path, file_time)| ReportEntry::new(path, Some(file_time), Outcome::Skipped { reason: "retention policy".to_string() })));
+    report_entries.extend(delete_files(cli, &files_to_delete));
+
+    let empty_folders_symlink_guard = SymlinkGuard::new();
+    let (_, empty_folder_entries) = delete_empty_folders_in_target_folders(cli, &empty_folders_symlink_guard)?;
+    // Only folders actually removed count, not dry-run previews or failures.
+    let empty_folders_deleted = empty_folder_entries.iter().filter(|entry| matches!(entry.outcome, Outcome::Deleted)).count();
+    report_entries.extend(empty_folder_entries);
+
+    if let Some(report_file) = &cli.report_file {
+        let report = RunReport::new(report_entries, empty_folders_deleted, started_at.elapsed());
+        report.write_to_file(report_file, cli.report_format)?;
+        info!("Wrote report to {}", report_file.display());
+    }
+
     Ok(())
 }
 
+/// Re-runs `run_pass` every `interval` instead of exiting after one pass,
+/// so ChronoClean can replace a cron entry. A target folder that has
+/// disappeared between passes is simply skipped by the scan, not an error.
+/// Shuts down cleanly on SIGINT/SIGTERM, finishing the in-progress pass
+/// first.
+fn run_watch_loop(cli: &Cli, interval: Duration) -> Result<()> {
+    let should_stop = Arc::new(AtomicBool::new(false));
+    // `signal_hook` is used instead of `ctrlc` because it handles SIGTERM
+    // unconditionally, whereas `ctrlc` only does so behind its optional
+    // `termination` feature.
+    signal_hook::flag::register(signal_hook::consts::SIGINT, should_stop.clone())?;
+    signal_hook::flag::register(signal_hook::consts::SIGTERM, should_stop.clone())?;
+
+    info!("Watch mode enabled, re-scanning every {}", humantime::format_duration(interval));
+
+    while !should_stop.load(Ordering::SeqCst) {
+        if let Err(e) = run_pass(cli) {
+            error!("Pass failed: {:?}", e);
+        }
+
+        if should_stop.load(Ordering::SeqCst) {
+            break;
+        }
+
+        debug!("Sleeping for {} before the next pass", humantime::format_duration(interval));
+        sleep_interruptible(interval, &should_stop);
+    }
+
+    info!("Watch mode stopped");
+    Ok(())
+}
+
+/// Sleeps for `duration`, checking `should_stop` frequently so a shutdown
+/// signal received mid-sleep is honored promptly instead of after the full
+/// interval elapses.
+fn sleep_interruptible(duration: Duration, should_stop: &AtomicBool) {
+    const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+    let mut remaining = duration;
+    while remaining > Duration::ZERO && !should_stop.load(Ordering::SeqCst) {
+        let sleep_for = POLL_INTERVAL.min(remaining);
+        thread::sleep(sleep_for);
+        remaining = remaining.saturating_sub(sleep_for);
+    }
+}
+
 fn validate_arguments(cli: &Cli) -> Result<()> {
     for target_folder in cli.target_folders.iter() {
         if !target_folder.exists() {
@@ -86,108 +256,448 @@ fn validate_arguments(cli: &Cli) -> Result<()> {
             }
         }
     }
+
+    if let Some(ignore_file) = &cli.ignore_file {
+        if !ignore_file.is_file() {
+            return Err(eyre::eyre!(format!("The ignore file does not exist: {}", ignore_file.display())));
+        }
+    }
     
     if let (Some(min_depth), Some(max_depth)) = (cli.min_depth, cli.max_depth) {
         if min_depth > max_depth {
             return Err(eyre::eyre!("The minimum depth must be less than or equal to the maximum depth"));
         }
     }
-    
+
+    if cli.keep_newest.is_some() && cli.keep_oldest.is_some() {
+        return Err(eyre::eyre!("--keep-newest and --keep-oldest are mutually exclusive"));
+    }
+    if let Some(keep_newest) = cli.keep_newest {
+        if keep_newest < 1 {
+            return Err(eyre::eyre!("--keep-newest must be at least 1"));
+        }
+    }
+    if let Some(keep_oldest) = cli.keep_oldest {
+        if keep_oldest < 1 {
+            return Err(eyre::eyre!("--keep-oldest must be at least 1"));
+        }
+    }
+
+    if cli.delete_method == DeleteMethod::Move && cli.archive_dir.is_none() {
+        return Err(eyre::eyre!("--archive-dir is required when --delete-method is move"));
+    }
+    if let Some(archive_dir) = &cli.archive_dir {
+        if cli.target_folders.iter().any(|target_folder| archive_dir.starts_with(target_folder)) {
+            return Err(eyre::eyre!("--archive-dir must not be inside a target folder, or already-archived files would be re-scanned and re-archived on every run"));
+        }
+        if cli.target_folders.iter().any(|target_folder| target_folder.starts_with(archive_dir)) {
+            return Err(eyre::eyre!("--archive-dir must not contain a target folder, or the whole target folder would be treated as already-archived and never scanned"));
+        }
+    }
+
+    if let (Some(min_size), Some(max_size)) = (cli.min_size, cli.max_size) {
+        if min_size > max_size {
+            return Err(eyre::eyre!("--min-size must be less than or equal to --max-size"));
+        }
+    }
+
+    if let Some(watch) = cli.watch {
+        if watch.is_zero() {
+            return Err(eyre::eyre!("--watch must be a positive duration"));
+        }
+    }
+
     Ok(())
 }
 
 fn print_arguments(cli: &Cli) {
-    log!("These are the arguments you provided:");
-    log!("Delete before: {}", humantime::format_duration(cli.delete_before));
-    log!("Target folders: {:?}", cli.target_folders.iter().map(|p| p.display()).collect::<Vec<_>>());
-    log!("Finding files to delete by their: {:?}", cli.file_date_types);
+    info!("These are the arguments you provided:");
+    info!("Delete before: {}", humantime::format_duration(cli.delete_before));
+    info!("Target folders: {:?}", cli.target_folders.iter().map(|p| p.display()).collect::<Vec<_>>());
+    info!("Finding files to delete by their: {:?}", cli.file_date_types);
     if let Some(ignored_paths) = &cli.ignored_paths {
-        log!("Ignored paths: {:?}", ignored_paths.iter().map(|p| p.display()).collect::<Vec<_>>());
+        info!("Ignored paths: {:?}", ignored_paths.iter().map(|p| p.display()).collect::<Vec<_>>());
+    }
+    if let Some(ignore_file) = &cli.ignore_file {
+        info!("Ignore file: {}", ignore_file.display());
     }
     if let Some(min_depth) = cli.min_depth {
-        log!("Min depth: {}", min_depth);
+        info!("Min depth: {}", min_depth);
     }
     if let Some(max_depth) = cli.max_depth {
-        log!("Max depth: {}", max_depth);
+        info!("Max depth: {}", max_depth);
+    }
+    info!("Delete empty folders: {}", cli.delete_empty_folders);
+    info!("Follow symbolic links: {}", cli.follow_symbolic_links);
+    if let Some(keep_newest) = cli.keep_newest {
+        info!("Keep newest: {}", keep_newest);
+    }
+    if let Some(keep_oldest) = cli.keep_oldest {
+        info!("Keep oldest: {}", keep_oldest);
+    }
+    info!("Delete method: {:?}", cli.delete_method);
+    if let Some(archive_dir) = &cli.archive_dir {
+        info!("Archive dir: {}", archive_dir.display());
+    }
+    info!("Dry run: {}", cli.dry_run);
+    if let Some(report_file) = &cli.report_file {
+        info!("Report file: {} ({:?})", report_file.display(), cli.report_format);
+    }
+    if let Some(min_size) = cli.min_size {
+        info!("Min size: {}", ByteSize(min_size));
+    }
+    if let Some(max_size) = cli.max_size {
+        info!("Max size: {}", ByteSize(max_size));
+    }
+    if let Some(extensions) = &cli.extensions {
+        info!("Extensions: {:?}", extensions);
+    }
+    if let Some(excluded_extensions) = &cli.excluded_extensions {
+        info!("Excluded extensions: {:?}", excluded_extensions);
+    }
+    if let Some(name_globs) = &cli.name_globs {
+        info!("Name globs: {:?}", name_globs);
+    }
+    if let Some(watch) = cli.watch {
+        info!("Watch interval: {}", humantime::format_duration(watch));
     }
-    log!("Delete empty folders: {}", cli.delete_empty_folders);
-    log!("Follow symbolic links: {}", cli.follow_symbolic_links);
-    log!("Dry run: {}", cli.dry_run);
-    log!("");
 }
 
-fn get_files_to_delete(cli: &Cli) -> Result<Vec<PathBuf>> {
+fn get_files_to_delete(cli: &Cli, ignores: &IgnoreMatchers, symlink_guard: &SymlinkGuard, name_globs: Option<&GlobSet>) -> Result<(Vec<(PathBuf, SystemTime)>, Vec<ReportEntry>)> {
+    let now = SystemTime::now();
+    let cutoff = now - cli.delete_before;
+
+    info!("Finding files to delete in target folder...");
+
+    let mut entries = Vec::new();
+    for entry in walk_target_folders(&cli, symlink_guard) {
+        match entry {
+            Ok(entry) => entries.push(entry),
+            Err(e) => warn!("Failed to read entry: {:?}", e),
+        }
+    }
+
+    let entries_checked = Arc::new(AtomicUsize::new(0));
+    let progress = spawn_progress_reporter(entries_checked.clone(), entries.len(), Duration::from_millis(100));
+    let progress_printer = thread::spawn(move || {
+        for data in progress {
+            info!("Checked {} / {} entries", data.entries_checked, data.entries_to_check);
+        }
+    });
+
+    let results: Vec<Result<EntryCheck>> = entries
+        .par_iter()
+        .map(|entry| {
+            let result = check_entry_for_deletion(entry, cli, cutoff, ignores, name_globs);
+            entries_checked.fetch_add(1, Ordering::Relaxed);
+            result
+        })
+        .collect();
+
+    progress_printer.join().expect("progress printer thread panicked");
+
     let mut files_to_delete = Vec::new();
+    let mut skipped_entries = Vec::new();
+    for result in results {
+        match result {
+            Ok(EntryCheck::Candidate(path, file_time)) => files_to_delete.push((path, file_time)),
+            Ok(EntryCheck::Skipped { path, reason }) => {
+                skipped_entries.push(ReportEntry::new(path, None, Outcome::Skipped { reason }));
+            }
+            Ok(EntryCheck::NotApplicable) => {}
+            Err(e) => warn!("Failed to check entry: {:?}", e),
+        }
+    }
+    files_to_delete.sort_by(|(a, _), (b, _)| a.cmp(b));
 
-    let now = std::time::SystemTime::now();
-    let cutoff = now - cli.delete_before;
+    info!("Found {} files to delete", files_to_delete.len());
 
-    log!("Finding files to delete in target folder...");
+    Ok((files_to_delete, skipped_entries))
+}
 
-    for entry in walk_target_folders(&cli) {
-        if entry.is_err() {
-            log!("Failed to read entry: {:?}", entry.err().unwrap());
-            continue;
+/// Outcome of screening a single walked entry as a deletion candidate.
+enum EntryCheck {
+    /// Not ignored, and either not a file or not yet past the age cutoff —
+    /// not worth a report entry.
+    NotApplicable,
+    /// An entry excluded by an ignore rule (checked first, for files and
+    /// directories of any age), or a file past the cutoff excluded by a
+    /// size, extension or name filter, worth recording in the report.
+    Skipped { path: PathBuf, reason: String },
+    /// A file that passed every check and is eligible for deletion, along
+    /// with the file time already read for the cutoff check, so later
+    /// pipeline stages never have to read its timestamps again.
+    Candidate(PathBuf, SystemTime),
+}
+
+fn check_entry_for_deletion(entry: &DirEntry, cli: &Cli, cutoff: SystemTime, ignores: &IgnoreMatchers, name_globs: Option<&GlobSet>) -> Result<EntryCheck> {
+    let path = entry.path();
+
+    let is_inside_ignored_folder = cli.ignored_paths.as_ref()
+        .map_or(false, |ignored_paths| ignored_paths.iter().any(|ignored_path| path.starts_with(ignored_path)));
+    let is_inside_archive_dir = cli.archive_dir.as_ref()
+        .map_or(false, |archive_dir| path.starts_with(archive_dir));
+    if is_inside_ignored_folder || is_inside_archive_dir || ignores.is_ignored(path) {
+        return Ok(EntryCheck::Skipped { path: path.to_path_buf(), reason: "ignored path".to_string() });
+    }
+
+    if !path.is_file() {
+        return Ok(EntryCheck::NotApplicable);
+    }
+
+    let snapshot = read_metadata_snapshot(path)?;
+    let file_time = snapshot.file_time(cli);
+
+    if file_time > cutoff {
+        return Ok(EntryCheck::NotApplicable);
+    }
+
+    if let Some(reason) = failing_filter_reason(cli, path, snapshot.size, name_globs) {
+        return Ok(EntryCheck::Skipped { path: path.to_path_buf(), reason });
+    }
+
+    Ok(EntryCheck::Candidate(path.to_path_buf(), file_time))
+}
+
+/// A single `metadata` call's worth of data, reused by both the age cutoff
+/// check and the size filter so neither needs a `metadata` call of its own.
+struct FileMetadataSnapshot {
+    created: SystemTime,
+    modified: SystemTime,
+    accessed: SystemTime,
+    size: u64,
+}
+
+impl FileMetadataSnapshot {
+    /// The most recent of whichever `--file-date-types` were requested.
+    fn file_time(&self, cli: &Cli) -> SystemTime {
+        cli.file_date_types.iter()
+            .map(|t| match t {
+                FileDateType::Created => self.created,
+                FileDateType::Modified => self.modified,
+                FileDateType::Accessed => self.accessed,
+            }).max()
+            .expect("At least one file date type must is provided")
+    }
+}
+
+fn read_metadata_snapshot(path: &Path) -> Result<FileMetadataSnapshot> {
+    let metadata = path.metadata()?;
+    Ok(FileMetadataSnapshot {
+        created: metadata.created()?,
+        modified: metadata.modified()?,
+        accessed: metadata.accessed()?,
+        size: metadata.len(),
+    })
+}
+
+/// Applies `--min-size`/`--max-size`, `--extensions`/`--excluded-extensions`,
+/// and `--name-globs` to a deletion candidate that already passed the age
+/// cutoff, returning why it was excluded, if at all.
+fn failing_filter_reason(cli: &Cli, path: &Path, size: u64, name_globs: Option<&GlobSet>) -> Option<String> {
+    if let Some(min_size) = cli.min_size {
+        if size < min_size {
+            return Some("size filter".to_string());
+        }
+    }
+    if let Some(max_size) = cli.max_size {
+        if size > max_size {
+            return Some("size filter".to_string());
         }
+    }
 
-        let entry = entry?;
-        let path = entry.path();
+    let extension = path.extension().and_then(|e| e.to_str());
 
-        let is_inside_ignored_folder = cli.ignored_paths.as_ref()
-            .map_or(false, |ignored_paths| ignored_paths.iter().any(|ignored_path| path.starts_with(ignored_path)));
-        if is_inside_ignored_folder {
-            continue;
+    if let Some(extensions) = &cli.extensions {
+        let matches = extension.map_or(false, |ext| extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)));
+        if !matches {
+            return Some("extension filter".to_string());
         }
+    }
 
-        if path.is_file() {
-            let metadata = path.metadata()?;
-
-            let created = metadata.created()?;
-            let modified = metadata.modified()?;
-            let accessed = metadata.accessed()?;
-
-            let file_time = cli.file_date_types.iter()
-                .map(|t| match t {
-                    FileDateType::Created => created,
-                    FileDateType::Modified => modified,
-                    FileDateType::Accessed => accessed,
-                }).max()
-                .expect("At least one file date type must is provided");
-            
-            if file_time <= cutoff {
-                files_to_delete.push(path.to_path_buf());
-            }
+    if let Some(excluded_extensions) = &cli.excluded_extensions {
+        let excluded = extension.map_or(false, |ext| excluded_extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)));
+        if excluded {
+            return Some("excluded extension filter".to_string());
+        }
+    }
+
+    if let Some(name_globs) = name_globs {
+        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        if !name_globs.is_match(file_name) {
+            return Some("name glob filter".to_string());
         }
     }
-    log!("Found {} files to delete", files_to_delete.len());
 
-    Ok(files_to_delete)
+    None
 }
 
-fn delete_files(cli: &Cli, files_to_delete: &[PathBuf]) {
-    log!("Deleting files...");
+/// Compiles `--name-globs` into a single matcher, once, up front.
+fn build_name_globs(patterns: &Option<Vec<String>>) -> Result<Option<GlobSet>> {
+    let Some(patterns) = patterns else {
+        return Ok(None);
+    };
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern)?);
+    }
+    Ok(Some(builder.build()?))
+}
+
+/// Applies `--keep-newest`/`--keep-oldest`, sparing the N newest (or oldest)
+/// deletion candidates in each parent directory even though they are past
+/// the age cutoff.
+/// Splits deletion candidates into those that should still be deleted and
+/// those spared by `--keep-newest`/`--keep-oldest`, along with the timestamp
+/// used to make that call.
+fn apply_retention_policy(cli: &Cli, files_to_delete: Vec<(PathBuf, SystemTime)>) -> (Vec<(PathBuf, SystemTime)>, Vec<(PathBuf, SystemTime)>) {
+    let Some((keep_count, keep_newest)) = cli.keep_newest.map(|n| (n, true))
+        .or_else(|| cli.keep_oldest.map(|n| (n, false))) else {
+        return (files_to_delete, Vec::new());
+    };
+
+    let mut groups: HashMap<PathBuf, Vec<(PathBuf, SystemTime)>> = HashMap::new();
+    for (path, file_time) in files_to_delete {
+        let parent = path.parent().unwrap_or_else(|| Path::new("")).to_path_buf();
+        groups.entry(parent).or_default().push((path, file_time));
+    }
+
+    let mut files_to_delete = Vec::new();
+    let mut retained = Vec::new();
+    for (_, mut group) in groups {
+        group.sort_by_key(|(_, file_time)| *file_time);
+
+        let kept = if keep_newest {
+            // The newest files are at the end once sorted ascending by time.
+            let split_at = group.len().saturating_sub(keep_count);
+            group.split_off(split_at)
+        } else if group.len() > keep_count {
+            // The oldest files are at the start once sorted ascending by time.
+            group.drain(0..keep_count).collect()
+        } else {
+            std::mem::take(&mut group)
+        };
+
+        files_to_delete.extend(group);
+        retained.extend(kept);
+    }
+    files_to_delete.sort_by(|(a, _), (b, _)| a.cmp(b));
+    retained.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    (files_to_delete, retained)
+}
+
+fn delete_files(cli: &Cli, files_to_delete: &[(PathBuf, SystemTime)]) -> Vec<ReportEntry> {
+    info!("Deleting files...");
 
     let max = files_to_delete.len();
+    let mut report_entries = Vec::with_capacity(max);
 
-    for (index, path) in files_to_delete.iter().enumerate() {
-        if cli.dry_run {
-            log!("{}/{}. Would delete file: {}", index + 1, max, path.display());
+    for (index, (path, file_time)) in files_to_delete.iter().enumerate() {
+        let outcome = if cli.dry_run {
+            info!("{}/{}. Would delete file: {}", index + 1, max, path.display());
+            Outcome::Skipped { reason: "dry run".to_string() }
         } else {
-            log!("{}/{}. Deleting file: {}", index + 1, max, path.display());
-            if let Err(e) = trash::delete(path) {
-                log!("Failed to move file '{}' to trash: {:?}", path.display(), e);
+            info!("{}/{}. Deleting file: {}", index + 1, max, path.display());
+            match delete_path(cli, path) {
+                Ok(()) => Outcome::Deleted,
+                Err(e) => {
+                    error!("Failed to delete file '{}': {:?}", path.display(), e);
+                    Outcome::Failed { error: e.to_string() }
+                }
             }
+        };
+
+        report_entries.push(ReportEntry::new(path.clone(), Some(*file_time), outcome));
+    }
+
+    info!("Finish deleting files");
+    report_entries
+}
+
+/// Deletes (or relocates) a single path according to `--delete-method`.
+fn delete_path(cli: &Cli, path: &Path) -> Result<()> {
+    match cli.delete_method {
+        DeleteMethod::Trash => trash::delete(path).map_err(|e| eyre::eyre!(e)),
+        DeleteMethod::Permanent => {
+            let is_symlink = fs::symlink_metadata(path).map(|m| m.file_type().is_symlink()).unwrap_or(false);
+            if is_symlink {
+                // `fs::remove_dir` refuses symlinks (even to an empty
+                // directory) with `NotADirectory`, so remove the link
+                // itself rather than the directory it points to.
+                fs::remove_file(path)?;
+            } else if path.is_dir() {
+                fs::remove_dir(path)?;
+            } else {
+                fs::remove_file(path)?;
+            }
+            Ok(())
         }
+        DeleteMethod::Move => move_to_archive(cli, path),
+    }
+}
+
+/// Relocates `path` into `--archive-dir`, preserving its path relative to
+/// whichever target folder it was found under, creating intermediate
+/// directories as needed and suffixing the name on collision.
+fn move_to_archive(cli: &Cli, path: &Path) -> Result<()> {
+    let archive_dir = cli.archive_dir.as_ref()
+        .ok_or_else(|| eyre::eyre!("--archive-dir is required when --delete-method is move"))?;
+
+    let relative_path = cli.target_folders.iter()
+        .find_map(|target_folder| path.strip_prefix(target_folder).ok())
+        .ok_or_else(|| eyre::eyre!("'{}' is not inside any target folder, refusing to archive it", path.display()))?;
+
+    let destination = archive_dir.join(relative_path);
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)?;
     }
+    let destination = avoid_collision(destination);
 
-    log!("Finish deleting files");
+    if let Err(e) = fs::rename(path, &destination) {
+        if e.raw_os_error() == Some(libc::EXDEV) {
+            debug!("Rename across devices failed for '{}', falling back to copy-then-remove", path.display());
+            fs::copy(path, &destination)?;
+            fs::remove_file(path)?;
+        } else {
+            return Err(e.into());
+        }
+    }
+    Ok(())
 }
 
-fn walk_target_folders(cli: &Cli) -> impl Iterator<Item = Result<DirEntry>> + use<'_> {
-    fn walk_folder(
+/// Appends an incrementing `" (N)"` suffix to `path`'s file stem until it no
+/// longer collides with an existing file.
+fn avoid_collision(path: PathBuf) -> PathBuf {
+    if !path.exists() {
+        return path;
+    }
+
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("file");
+    let extension = path.extension().and_then(|s| s.to_str());
+    let parent = path.parent().unwrap_or_else(|| Path::new("")).to_path_buf();
+
+    let mut counter = 1;
+    loop {
+        let candidate_name = match extension {
+            Some(extension) => format!("{stem} ({counter}).{extension}"),
+            None => format!("{stem} ({counter})"),
+        };
+        let candidate = parent.join(candidate_name);
+        if !candidate.exists() {
+            return candidate;
+        }
+        counter += 1;
+    }
+}
+
+fn walk_target_folders<'a>(cli: &'a Cli, symlink_guard: &'a SymlinkGuard) -> impl Iterator<Item = Result<DirEntry>> + use<'a> {
+    fn walk_folder<'a>(
         folder: &Path,
-        cli: &Cli,
-    ) -> Option<impl Iterator<Item = Result<DirEntry>>> {
+        cli: &'a Cli,
+        symlink_guard: &'a SymlinkGuard,
+    ) -> Option<impl Iterator<Item = Result<DirEntry>> + 'a> {
         if !folder.is_dir() {
             return None;
         }
@@ -200,28 +710,44 @@ fn walk_target_folders(cli: &Cli) -> impl Iterator<Item = Result<DirEntry>> + us
             walk = walk.max_depth(max_depth);
         }
 
-        Some(walk.into_iter().map(|e| e.map_err(|e| eyre::eyre!(e))))
+        let follow_symbolic_links = cli.follow_symbolic_links;
+        Some(walk.into_iter()
+            .filter_entry(move |entry| {
+                if !follow_symbolic_links || !entry.path_is_symlink() {
+                    return true;
+                }
+                match symlink_guard.check(entry.path()) {
+                    Ok(()) => true,
+                    Err(issue) => {
+                        warn!("Not following symlink ({}): {}", issue, entry.path().display());
+                        false
+                    }
+                }
+            })
+            .map(|e| e.map_err(|e| eyre::eyre!(e))))
     }
-    
+
     cli.target_folders.iter()
-        .flat_map(|e| walk_folder(e, cli).into_iter().flatten())
+        .flat_map(move |e| walk_folder(e, cli, symlink_guard).into_iter().flatten())
 }
 
-fn delete_empty_folders_in_target_folders(cli: &Cli) -> Result<()> {
+fn delete_empty_folders_in_target_folders(cli: &Cli, symlink_guard: &SymlinkGuard) -> Result<(usize, Vec<ReportEntry>)> {
     if !cli.delete_empty_folders {
-        return Ok(());
+        return Ok((0, Vec::new()));
     }
-    
+
     let counter = AtomicU32::new(0);
-    log!("\nDeleting empty folders...");
+    let mut report_entries = Vec::new();
+    info!("Deleting empty folders...");
     for target_folder in cli.target_folders.iter() {
-        delete_empty_folders(&target_folder, &cli, &counter)?;
+        delete_empty_folders(&target_folder, &cli, &counter, symlink_guard, &mut report_entries)?;
     }
-    log!("Deleted {} empty folders", counter.load(Ordering::Relaxed));
-    Ok(())
+    let deleted = counter.load(Ordering::Relaxed) as usize;
+    info!("Deleted {} empty folders", deleted);
+    Ok((deleted, report_entries))
 }
 
-fn delete_empty_folders(path: &Path, cli: &Cli, counter: &AtomicU32) -> Result<()> {
+fn delete_empty_folders(path: &Path, cli: &Cli, counter: &AtomicU32, symlink_guard: &SymlinkGuard, report_entries: &mut Vec<ReportEntry>) -> Result<()> {
     if !path.is_dir() {
         return Ok(());
     }
@@ -229,7 +755,7 @@ fn delete_empty_folders(path: &Path, cli: &Cli, counter: &AtomicU32) -> Result<(
     let mut is_empty = true;
     for entry in fs::read_dir(path)? {
         if entry.is_err() {
-            log!("Failed to read entry in {}: {:?}", path.display(), entry.err().unwrap());
+            warn!("Failed to read entry in {}: {:?}", path.display(), entry.err().unwrap());
             continue;
         }
 
@@ -237,14 +763,22 @@ fn delete_empty_folders(path: &Path, cli: &Cli, counter: &AtomicU32) -> Result<(
         let entry_path = entry.path();
         let file_type = entry.file_type()?;
 
-        if !cli.follow_symbolic_links && file_type.is_symlink() {
-            is_empty = false;
-            continue;
+        if file_type.is_symlink() {
+            if !cli.follow_symbolic_links {
+                is_empty = false;
+                continue;
+            }
+
+            if let Err(issue) = symlink_guard.check(&entry_path) {
+                warn!("Not following symlink ({}): {}", issue, entry_path.display());
+                is_empty = false;
+                continue;
+            }
         }
 
         if entry_path.is_dir() {
             // Recursively delete empty subfolders
-            delete_empty_folders(&entry_path, cli, counter)?;
+            delete_empty_folders(&entry_path, cli, counter, symlink_guard, report_entries)?;
         } else {
             // If there's a file, the folder is not empty
             is_empty = false;
@@ -253,23 +787,145 @@ fn delete_empty_folders(path: &Path, cli: &Cli, counter: &AtomicU32) -> Result<(
 
     // If the folder is empty after processing, delete it
     if is_empty && path.read_dir()?.next().is_none() {
-        delete_empty_folder(path, cli, counter)?;
+        report_entries.push(delete_empty_folder(path, cli, counter));
     }
     Ok(())
 }
 
-fn delete_empty_folder(path: &Path, cli: &Cli, counter: &AtomicU32) -> Result<()> {
+/// Deletes (or previews deleting) a single empty folder, returning its
+/// outcome as a `ReportEntry` the same way `delete_files` does for files.
+fn delete_empty_folder(path: &Path, cli: &Cli, counter: &AtomicU32) -> ReportEntry {
     if !path.exists() {
-        log!("Warning: tried to delete a path that does not exist: {}", path.display());
-        return Ok(());
+        warn!("Tried to delete a path that does not exist: {}", path.display());
+        return ReportEntry::new(path.to_path_buf(), None, Outcome::Skipped { reason: "already gone".to_string() });
     }
 
     let count = counter.fetch_add(1, Ordering::Relaxed);
-    if cli.dry_run {
-        log!("{}. Would delete empty folder: {}", count + 1, path.display());
+    let outcome = if cli.dry_run {
+        info!("{}. Would delete empty folder: {}", count + 1, path.display());
+        Outcome::Skipped { reason: "dry run".to_string() }
     } else {
-        log!("{}. Deleting empty folder: {}", count + 1, path.display());
-        trash::delete(path)?;
+        info!("{}. Deleting empty folder: {}", count + 1, path.display());
+        match delete_path(cli, path) {
+            Ok(()) => Outcome::Deleted,
+            Err(e) => {
+                error!("Failed to delete empty folder '{}': {:?}", path.display(), e);
+                Outcome::Failed { error: e.to_string() }
+            }
+        }
+    };
+
+    ReportEntry::new(path.to_path_buf(), None, outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cli_with_retention(keep_newest: Option<usize>, keep_oldest: Option<usize>) -> Cli {
+        Cli {
+            delete_before: Duration::from_secs(0),
+            target_folders: vec![],
+            file_date_types: vec![FileDateType::Modified],
+            ignored_paths: None,
+            ignore_file: None,
+            min_depth: None,
+            max_depth: None,
+            delete_empty_folders: false,
+            follow_symbolic_links: false,
+            keep_newest,
+            keep_oldest,
+            delete_method: DeleteMethod::Trash,
+            archive_dir: None,
+            dry_run: false,
+            verbose: 0,
+            quiet: false,
+            log_file: None,
+            report_file: None,
+            report_format: ReportFormat::Json,
+            min_size: None,
+            max_size: None,
+            extensions: None,
+            excluded_extensions: None,
+            name_globs: None,
+            watch: None,
+        }
     }
-    Ok(())
-}
\ No newline at end of file
+
+    fn file_at(secs_ago: u64, dir: &str, name: &str) -> (PathBuf, SystemTime) {
+        let path = PathBuf::from(dir).join(name);
+        let time = SystemTime::now() - Duration::from_secs(secs_ago);
+        (path, time)
+    }
+
+    #[test]
+    fn keep_newest_spares_the_newest_n_per_directory() {
+        let cli = cli_with_retention(Some(1), None);
+        let files = vec![
+            file_at(300, "/a", "old.txt"),
+            file_at(200, "/a", "mid.txt"),
+            file_at(100, "/a", "new.txt"),
+        ];
+
+        let (to_delete, retained) = apply_retention_policy(&cli, files);
+
+        assert_eq!(to_delete.len(), 2);
+        assert_eq!(retained.len(), 1);
+        assert_eq!(retained[0].0, PathBuf::from("/a/new.txt"));
+    }
+
+    #[test]
+    fn keep_oldest_spares_the_oldest_n_per_directory() {
+        let cli = cli_with_retention(None, Some(1));
+        let files = vec![
+            file_at(300, "/a", "old.txt"),
+            file_at(200, "/a", "mid.txt"),
+            file_at(100, "/a", "new.txt"),
+        ];
+
+        let (to_delete, retained) = apply_retention_policy(&cli, files);
+
+        assert_eq!(to_delete.len(), 2);
+        assert_eq!(retained.len(), 1);
+        assert_eq!(retained[0].0, PathBuf::from("/a/old.txt"));
+    }
+
+    #[test]
+    fn retention_policy_is_scoped_per_parent_directory() {
+        let cli = cli_with_retention(Some(1), None);
+        let files = vec![
+            file_at(300, "/a", "one.txt"),
+            file_at(300, "/b", "two.txt"),
+        ];
+
+        let (to_delete, retained) = apply_retention_policy(&cli, files);
+
+        assert!(to_delete.is_empty());
+        assert_eq!(retained.len(), 2);
+    }
+
+    #[test]
+    fn no_retention_flags_returns_all_candidates_for_deletion() {
+        let cli = cli_with_retention(None, None);
+        let files = vec![file_at(300, "/a", "one.txt")];
+
+        let (to_delete, retained) = apply_retention_policy(&cli, files);
+
+        assert_eq!(to_delete.len(), 1);
+        assert!(retained.is_empty());
+    }
+
+    #[test]
+    fn keep_count_larger_than_group_keeps_everything() {
+        let cli = cli_with_retention(Some(5), None);
+        let files = vec![
+            file_at(300, "/a", "one.txt"),
+            file_at(200, "/a", "two.txt"),
+        ];
+
+        let (to_delete, retained) = apply_retention_policy(&cli, files);
+
+        assert!(to_delete.is_empty());
+        assert_eq!(retained.len(), 2);
+    }
+}
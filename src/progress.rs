@@ -0,0 +1,37 @@
+use crossbeam_channel::{bounded, Receiver};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// Snapshot of how far a scan has progressed.
+#[derive(Debug, Clone, Copy)]
+pub struct ProgressData {
+    pub entries_checked: usize,
+    pub entries_to_check: usize,
+}
+
+/// Spawns a background thread that ticks every `interval` and reports the
+/// current value of `checked` against `total`, until `checked` reaches
+/// `total`. The returned receiver yields at most one `ProgressData` per
+/// tick; a tick is dropped while the previous one is still unread.
+pub fn spawn_progress_reporter(
+    checked: Arc<AtomicUsize>,
+    total: usize,
+    interval: Duration,
+) -> Receiver<ProgressData> {
+    let (sender, receiver) = bounded(1);
+
+    thread::spawn(move || loop {
+        let entries_checked = checked.load(Ordering::Relaxed);
+        // A full channel just means the last tick hasn't been read yet, skip this one.
+        let _ = sender.try_send(ProgressData { entries_checked, entries_to_check: total });
+
+        if entries_checked >= total {
+            break;
+        }
+        thread::sleep(interval);
+    });
+
+    receiver
+}
@@ -0,0 +1,126 @@
+use color_eyre::eyre::Result;
+use serde::Serialize;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// What happened to a single file or folder considered for deletion.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Outcome {
+    Deleted,
+    Skipped { reason: String },
+    Failed { error: String },
+}
+
+impl Outcome {
+    fn label(&self) -> &'static str {
+        match self {
+            Outcome::Deleted => "deleted",
+            Outcome::Skipped { .. } => "skipped",
+            Outcome::Failed { .. } => "failed",
+        }
+    }
+
+    fn detail(&self) -> Option<&str> {
+        match self {
+            Outcome::Deleted => None,
+            Outcome::Skipped { reason } => Some(reason),
+            Outcome::Failed { error } => Some(error),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportEntry {
+    pub path: PathBuf,
+    pub file_time: Option<String>,
+    pub outcome: Outcome,
+}
+
+impl ReportEntry {
+    pub fn new(path: PathBuf, file_time: Option<SystemTime>, outcome: Outcome) -> Self {
+        Self {
+            path,
+            file_time: file_time.map(|time| humantime::format_rfc3339_seconds(time).to_string()),
+            outcome,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+    Json,
+    Csv,
+}
+
+pub fn report_format_parser(value: &str) -> std::result::Result<ReportFormat, String> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "json" => Ok(ReportFormat::Json),
+        "csv" => Ok(ReportFormat::Csv),
+        other => Err(format!("Unsupported report format: {}. Please use one of the following: json, csv", other)),
+    }
+}
+
+/// Full record of a run, written to `--report-file` for scripting/auditing.
+#[derive(Debug, Serialize)]
+pub struct RunReport {
+    pub entries: Vec<ReportEntry>,
+    /// Entries with a `deleted` outcome, empty folders included.
+    pub files_deleted: usize,
+    /// Entries with a `failed` outcome, empty folders included.
+    pub files_failed: usize,
+    pub empty_folders_deleted: usize,
+    pub elapsed_seconds: f64,
+}
+
+impl RunReport {
+    pub fn new(entries: Vec<ReportEntry>, empty_folders_deleted: usize, elapsed: Duration) -> Self {
+        let files_deleted = entries.iter().filter(|entry| matches!(entry.outcome, Outcome::Deleted)).count();
+        let files_failed = entries.iter().filter(|entry| matches!(entry.outcome, Outcome::Failed { .. })).count();
+
+        Self {
+            entries,
+            files_deleted,
+            files_failed,
+            empty_folders_deleted,
+            elapsed_seconds: elapsed.as_secs_f64(),
+        }
+    }
+
+    pub fn write_to_file(&self, path: &Path, format: ReportFormat) -> Result<()> {
+        match format {
+            ReportFormat::Json => {
+                let file = File::create(path)?;
+                serde_json::to_writer_pretty(file, self)?;
+            }
+            ReportFormat::Csv => self.write_csv(path)?,
+        }
+        Ok(())
+    }
+
+    fn write_csv(&self, path: &Path) -> Result<()> {
+        let mut file = File::create(path)?;
+        writeln!(file, "path,file_time,status,detail")?;
+        for entry in &self.entries {
+            writeln!(
+                file,
+                "{},{},{},{}",
+                csv_field(&entry.path.display().to_string()),
+                csv_field(entry.file_time.as_deref().unwrap_or("")),
+                entry.outcome.label(),
+                csv_field(entry.outcome.detail().unwrap_or("")),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains(',') || value.contains('"') || value.contains('\n') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Number of symlink indirections a single traversal branch is allowed to
+/// follow before it is assumed to be a loop rather than a deep chain.
+const MAX_SYMLINK_JUMPS: usize = 20;
+
+#[derive(Debug)]
+pub enum SymlinkIssue {
+    /// The link resolves back into a directory already seen in this walk, or
+    /// reaching it takes more than `MAX_SYMLINK_JUMPS` symlinks.
+    InfiniteRecursion(PathBuf),
+    /// The link's target could not be resolved (dangling symlink).
+    NonExistentFile(PathBuf),
+}
+
+impl fmt::Display for SymlinkIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SymlinkIssue::InfiniteRecursion(path) => write!(f, "infinite recursion back to {}", path.display()),
+            SymlinkIssue::NonExistentFile(path) => write!(f, "dangling symlink to {}", path.display()),
+        }
+    }
+}
+
+/// Tracks canonicalized directories visited while following symbolic links,
+/// so `walk_target_folders` and `delete_empty_folders` can detect and break
+/// out of a symlink loop instead of recursing forever.
+pub struct SymlinkGuard {
+    visited_dirs: Mutex<HashSet<PathBuf>>,
+}
+
+impl SymlinkGuard {
+    pub fn new() -> Self {
+        Self { visited_dirs: Mutex::new(HashSet::new()) }
+    }
+
+    /// Checks whether it's safe to follow the symlink at `path`.
+    pub fn check(&self, path: &Path) -> Result<(), SymlinkIssue> {
+        if count_symlink_jumps(path) > MAX_SYMLINK_JUMPS {
+            return Err(SymlinkIssue::InfiniteRecursion(path.to_path_buf()));
+        }
+
+        let canonical = match path.canonicalize() {
+            Ok(canonical) => canonical,
+            Err(_) => return Err(SymlinkIssue::NonExistentFile(path.to_path_buf())),
+        };
+
+        if path.is_dir() {
+            let mut visited_dirs = self.visited_dirs.lock().unwrap();
+            if !visited_dirs.insert(canonical) {
+                return Err(SymlinkIssue::InfiniteRecursion(path.to_path_buf()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for SymlinkGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Counts how many components of `path`, from the root down, are themselves
+/// symlinks, i.e. how many indirections must be resolved to reach `path`.
+/// This is what `MAX_SYMLINK_JUMPS` is meant to bound, as opposed to the
+/// directory nesting depth reported by the walker.
+fn count_symlink_jumps(path: &Path) -> usize {
+    let mut jumps = 0;
+    let mut current = PathBuf::new();
+
+    for component in path.components() {
+        current.push(component);
+        if fs::symlink_metadata(&current).map(|metadata| metadata.file_type().is_symlink()).unwrap_or(false) {
+            jumps += 1;
+        }
+    }
+
+    jumps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    /// Creates a fresh temp directory for a test to work in, named after
+    /// `label` plus a timestamp so parallel test runs don't collide.
+    fn temp_dir(label: &str) -> PathBuf {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        let dir = std::env::temp_dir().join(format!("chronoclean_test_{label}_{nanos}"));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn count_symlink_jumps_counts_each_symlinked_component() {
+        let root = temp_dir("jumps");
+        let real_dir = root.join("real");
+        fs::create_dir(&real_dir).unwrap();
+        let link = root.join("link");
+        symlink(&real_dir, &link).unwrap();
+
+        assert_eq!(count_symlink_jumps(&real_dir), 0);
+        assert_eq!(count_symlink_jumps(&link), 1);
+        assert_eq!(count_symlink_jumps(&link.join("nested.txt")), 1);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn check_allows_a_symlink_seen_for_the_first_time() {
+        let root = temp_dir("first_visit");
+        let real_dir = root.join("real");
+        fs::create_dir(&real_dir).unwrap();
+        let link = root.join("link");
+        symlink(&real_dir, &link).unwrap();
+
+        let guard = SymlinkGuard::new();
+        assert!(guard.check(&link).is_ok());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn check_rejects_revisiting_the_same_canonical_directory() {
+        let root = temp_dir("loop");
+        let real_dir = root.join("real");
+        fs::create_dir(&real_dir).unwrap();
+        let link = root.join("link");
+        symlink(&real_dir, &link).unwrap();
+
+        let guard = SymlinkGuard::new();
+        assert!(guard.check(&link).is_ok());
+        assert!(matches!(guard.check(&link), Err(SymlinkIssue::InfiniteRecursion(_))));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn check_rejects_dangling_symlinks() {
+        let root = temp_dir("dangling");
+        let link = root.join("link");
+        symlink(root.join("does-not-exist"), &link).unwrap();
+
+        let guard = SymlinkGuard::new();
+        assert!(matches!(guard.check(&link), Err(SymlinkIssue::NonExistentFile(_))));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}